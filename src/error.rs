@@ -10,6 +10,16 @@ pub enum Error {
     #[error("Migration error: {0}")]
     Migration(#[from] lib_migrations_core::Error),
 
+    #[error(
+        "checksum mismatch for migration {version}: expected {expected}, found {found} \
+         (the SQL of an already-applied migration was edited)"
+    )]
+    ChecksumMismatch {
+        version: u64,
+        expected: String,
+        found: String,
+    },
+
     #[cfg(feature = "sqlite")]
     #[error("SQLite error: {0}")]
     Sqlite(#[from] rusqlite::Error),
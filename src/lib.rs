@@ -8,7 +8,10 @@ pub use error::{Error, Result};
 pub use migration::{SqlExecutor, SqlMigration};
 
 #[cfg(feature = "sqlite")]
-pub use sqlite::{SqliteContext, SqliteStore};
+pub use sqlite::{
+    Progress, SqliteContext, SqliteMigrationDb, SqliteStore, UserVersionStore,
+    SQLITE_MAX_VARIABLE_NUMBER,
+};
 
 // Re-export core types for convenience
 pub use lib_migrations_core::{
@@ -1,4 +1,5 @@
 use lib_migrations_core::{Migration, Phase};
+use sha2::{Digest, Sha256};
 
 /// Trait for SQL execution contexts.
 ///
@@ -8,6 +9,27 @@ pub trait SqlExecutor {
 
     /// Execute SQL statement(s)
     fn execute(&mut self, sql: &str) -> std::result::Result<(), Self::Error>;
+
+    /// Called before a `Phase::PostDeploy` migration is applied; the default
+    /// does nothing (see `SqliteContext::with_post_deploy_backups`).
+    fn before_post_deploy(&mut self, _version: u64) -> std::result::Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Apply `sql` for `version`, recording a changeset so
+    /// [`rollback_tracked`](SqlExecutor::rollback_tracked) can undo it later
+    /// without hand-written `down_sql`. The default just runs `sql` plainly
+    /// (no changeset support, so it won't be rollback-able).
+    fn apply_tracked(&mut self, version: u64, sql: &str) -> std::result::Result<(), Self::Error> {
+        let _ = version;
+        self.execute(sql)
+    }
+
+    /// Undo the changeset recorded by [`apply_tracked`](SqlExecutor::apply_tracked)
+    /// for `version`. The default returns `Ok(false)` (nothing to roll back).
+    fn rollback_tracked(&mut self, _version: u64) -> std::result::Result<bool, Self::Error> {
+        Ok(false)
+    }
 }
 
 /// A migration defined by SQL strings.
@@ -19,17 +41,23 @@ pub struct SqlMigration {
     phase: Phase,
     up_sql: String,
     down_sql: Option<String>,
+    session_rollback: bool,
+    checksum: String,
 }
 
 impl SqlMigration {
     /// Create a new SQL migration
     pub fn new(version: u64, name: impl Into<String>, up_sql: impl Into<String>) -> Self {
+        let up_sql = up_sql.into();
+        let checksum = checksum_of(&up_sql);
         Self {
             version,
             name: name.into(),
             phase: Phase::PreDeploy,
-            up_sql: up_sql.into(),
+            up_sql,
             down_sql: None,
+            session_rollback: false,
+            checksum,
         }
     }
 
@@ -45,6 +73,15 @@ impl SqlMigration {
         self
     }
 
+    /// Make this migration reversible via a session-recorded changeset
+    /// instead of hand-written `down_sql`. Only row-level `INSERT`/`UPDATE`/
+    /// `DELETE` changes are reversible this way, not schema DDL. Ignored if
+    /// `with_down` is also used; explicit `down_sql` always wins.
+    pub fn with_session_rollback(mut self) -> Self {
+        self.session_rollback = true;
+        self
+    }
+
     /// Get the up SQL
     pub fn up_sql(&self) -> &str {
         &self.up_sql
@@ -70,12 +107,33 @@ impl SqlMigration {
         self.phase
     }
 
-    /// Whether this migration has rollback SQL
+    /// Whether this migration has rollback SQL or is session-rollback-tracked
     pub fn has_rollback(&self) -> bool {
-        self.down_sql.is_some()
+        self.down_sql.is_some() || self.session_rollback
+    }
+
+    /// SHA-256 checksum of `up_sql`, hex-encoded.
+    ///
+    /// Stores that support drift detection persist this alongside each
+    /// applied version so that editing the SQL of a migration that has
+    /// already run on some database can be caught instead of silently
+    /// ignored.
+    pub fn checksum(&self) -> &str {
+        &self.checksum
     }
 }
 
+/// Compute the hex-encoded SHA-256 checksum of `sql`.
+fn checksum_of(sql: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(sql.as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
 impl<Ctx> Migration<Ctx> for SqlMigration
 where
     Ctx: SqlExecutor,
@@ -93,8 +151,16 @@ where
     }
 
     fn apply(&self, ctx: &mut Ctx) -> lib_migrations_core::Result<()> {
-        ctx.execute(&self.up_sql)
-            .map_err(|e| lib_migrations_core::Error::failed(self.version, e.to_string()))
+        if self.phase == Phase::PostDeploy {
+            ctx.before_post_deploy(self.version)
+                .map_err(|e| lib_migrations_core::Error::failed(self.version, e.to_string()))?;
+        }
+        if self.session_rollback && self.down_sql.is_none() {
+            ctx.apply_tracked(self.version, &self.up_sql)
+        } else {
+            ctx.execute(&self.up_sql)
+        }
+        .map_err(|e| lib_migrations_core::Error::failed(self.version, e.to_string()))
     }
 
     fn rollback(&self, ctx: &mut Ctx) -> lib_migrations_core::Result<()> {
@@ -102,6 +168,18 @@ where
             Some(sql) => ctx
                 .execute(sql)
                 .map_err(|e| lib_migrations_core::Error::failed(self.version, e.to_string())),
+            None if self.session_rollback => {
+                let rolled_back = ctx
+                    .rollback_tracked(self.version)
+                    .map_err(|e| lib_migrations_core::Error::failed(self.version, e.to_string()))?;
+                if rolled_back {
+                    Ok(())
+                } else {
+                    Err(lib_migrations_core::Error::RollbackNotSupported(
+                        self.version,
+                    ))
+                }
+            }
             None => Err(lib_migrations_core::Error::RollbackNotSupported(
                 self.version,
             )),
@@ -109,7 +187,7 @@ where
     }
 
     fn can_rollback(&self) -> bool {
-        self.down_sql.is_some()
+        self.down_sql.is_some() || self.session_rollback
     }
 }
 
@@ -137,6 +215,17 @@ mod tests {
         assert_eq!(migration.down_sql(), None);
     }
 
+    #[test]
+    fn test_sql_migration_checksum() {
+        let a = SqlMigration::new(1, "create_users", "CREATE TABLE users (id INTEGER)");
+        let b = SqlMigration::new(1, "create_users", "CREATE TABLE users (id INTEGER)");
+        let c = SqlMigration::new(1, "create_users", "CREATE TABLE users (id INTEGER, x TEXT)");
+
+        assert_eq!(a.checksum(), b.checksum());
+        assert_ne!(a.checksum(), c.checksum());
+        assert_eq!(a.checksum().len(), 64);
+    }
+
     #[test]
     fn test_sql_migration_phase() {
         let pre = SqlMigration::new(1, "add_column", "ALTER TABLE users ADD email TEXT");
@@ -146,4 +235,64 @@ mod tests {
             .phase(Phase::PostDeploy);
         assert_eq!(post.get_phase(), Phase::PostDeploy);
     }
+
+    #[derive(Default)]
+    struct RecordingExecutor {
+        post_deploy_hook_calls: u32,
+    }
+
+    impl SqlExecutor for RecordingExecutor {
+        type Error = std::convert::Infallible;
+
+        fn execute(&mut self, _sql: &str) -> std::result::Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn before_post_deploy(&mut self, _version: u64) -> std::result::Result<(), Self::Error> {
+            self.post_deploy_hook_calls += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_post_deploy_hook_runs_only_for_post_deploy_migrations() {
+        let mut ctx = RecordingExecutor::default();
+
+        let pre = SqlMigration::new(1, "add_column", "ALTER TABLE users ADD email TEXT");
+        Migration::apply(&pre, &mut ctx).unwrap();
+        assert_eq!(ctx.post_deploy_hook_calls, 0);
+
+        let post = SqlMigration::new(2, "drop_column", "ALTER TABLE users DROP old_column")
+            .phase(Phase::PostDeploy);
+        Migration::apply(&post, &mut ctx).unwrap();
+        assert_eq!(ctx.post_deploy_hook_calls, 1);
+    }
+
+    #[test]
+    fn test_session_rollback_flag_reports_reversible() {
+        let migration = SqlMigration::new(1, "backfill", "UPDATE users SET active = 1")
+            .with_session_rollback();
+
+        assert!(migration.has_rollback());
+        assert!(Migration::<RecordingExecutor>::can_rollback(&migration));
+        assert_eq!(migration.down_sql(), None);
+    }
+
+    #[test]
+    fn test_session_rollback_applies_via_apply_tracked_default() {
+        // A context with no changeset support still applies the migration
+        // fine via the default `apply_tracked` (which just runs the SQL);
+        // it just can't roll it back.
+        let mut ctx = RecordingExecutor::default();
+        let migration = SqlMigration::new(1, "backfill", "UPDATE users SET active = 1")
+            .with_session_rollback();
+
+        Migration::apply(&migration, &mut ctx).unwrap();
+
+        let err = Migration::rollback(&migration, &mut ctx).unwrap_err();
+        assert!(matches!(
+            err,
+            lib_migrations_core::Error::RollbackNotSupported(1)
+        ));
+    }
 }
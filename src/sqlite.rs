@@ -1,12 +1,78 @@
-use crate::migration::SqlExecutor;
+use crate::migration::{SqlExecutor, SqlMigration};
 use lib_migrations_core::{MigrationRecord, MigrationStore};
-use rusqlite::{params, Connection};
-use std::path::Path;
-use std::time::{SystemTime, UNIX_EPOCH};
+use rusqlite::{backup, params, Connection, OptionalExtension};
+use std::cell::{Cell, RefCell};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Progress of an in-flight `backup_to` operation.
+#[derive(Debug, Clone, Copy)]
+pub struct Progress {
+    /// Pages still to be copied.
+    pub remaining: i32,
+    /// Total pages in the source database as of this step.
+    pub total: i32,
+}
+
+/// Copy `src` to `dest_path` incrementally, `step_pages` pages at a time,
+/// using SQLite's online backup API so the source stays usable throughout.
+fn backup_connection(
+    src: &Connection,
+    dest_path: impl AsRef<Path>,
+    step_pages: i32,
+    mut progress: impl FnMut(Progress),
+) -> rusqlite::Result<()> {
+    let mut dst = Connection::open(dest_path)?;
+    let backup = backup::Backup::new(src, &mut dst)?;
+    backup.run_to_completion(step_pages, Duration::from_millis(0), Some(|p: backup::Progress| {
+        progress(Progress {
+            remaining: p.remaining,
+            total: p.pagecount,
+        });
+    }))
+}
+
+/// Where (and how) to snapshot a database before a `Phase::PostDeploy`
+/// migration runs.
+struct PostDeployBackupConfig {
+    dir: PathBuf,
+    step_pages: i32,
+}
+
+/// SQLite's default limit on bound parameters per statement
+/// (`SQLITE_MAX_VARIABLE_NUMBER`).
+pub const SQLITE_MAX_VARIABLE_NUMBER: usize = 999;
+
+/// Add the `checksum` column to an existing `_migrations` table that
+/// predates it, as a real migration step rather than relying on
+/// `CREATE TABLE IF NOT EXISTS` (a no-op against a table that already
+/// exists with the old three-column schema).
+fn ensure_checksum_column(conn: &Connection) -> rusqlite::Result<()> {
+    let has_checksum = conn
+        .prepare("PRAGMA table_info(_migrations)")?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<Vec<_>>>()?
+        .iter()
+        .any(|name| name == "checksum");
+
+    if !has_checksum {
+        conn.execute_batch("ALTER TABLE _migrations ADD COLUMN checksum TEXT NOT NULL DEFAULT ''")?;
+    }
+    Ok(())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
 
 /// SQLite execution context wrapping a connection.
 pub struct SqliteContext {
     conn: Connection,
+    post_deploy_backup: Option<PostDeployBackupConfig>,
 }
 
 impl SqliteContext {
@@ -18,13 +84,19 @@ impl SqliteContext {
              PRAGMA synchronous=NORMAL;
              PRAGMA foreign_keys=ON;",
         )?;
-        Ok(Self { conn })
+        Ok(Self {
+            conn,
+            post_deploy_backup: None,
+        })
     }
 
     /// Open an in-memory SQLite database
     pub fn open_in_memory() -> Result<Self, rusqlite::Error> {
         let conn = Connection::open_in_memory()?;
-        Ok(Self { conn })
+        Ok(Self {
+            conn,
+            post_deploy_backup: None,
+        })
     }
 
     /// Get the underlying connection
@@ -41,6 +113,76 @@ impl SqliteContext {
     pub fn into_connection(self) -> Connection {
         self.conn
     }
+
+    /// Copy this database to `dest_path` incrementally, `step_pages` pages
+    /// at a time, reporting progress through `progress`.
+    pub fn backup_to(
+        &self,
+        dest_path: impl AsRef<Path>,
+        step_pages: i32,
+        progress: impl FnMut(Progress),
+    ) -> rusqlite::Result<()> {
+        backup_connection(&self.conn, dest_path, step_pages, progress)
+    }
+
+    /// Snapshot the database to a timestamped file under `dir` before every
+    /// migration whose `phase()` is `Phase::PostDeploy`, which is where
+    /// irreversible drops typically live. Off by default.
+    pub fn with_post_deploy_backups(mut self, dir: impl Into<PathBuf>, step_pages: i32) -> Self {
+        self.post_deploy_backup = Some(PostDeployBackupConfig {
+            dir: dir.into(),
+            step_pages,
+        });
+        self
+    }
+
+    /// Run `sql_template_fn` against `items` in chunks sized to respect
+    /// SQLite's bound-parameter limit (`SQLITE_MAX_VARIABLE_NUMBER`), for
+    /// data backfills and bulk `DELETE ... WHERE id IN (...)`-style
+    /// statements. `reserved_params` are extra parameters bound before the
+    /// item list in every chunk. `sql_template_fn` receives the
+    /// comma-separated placeholder list for one chunk; `bind_fn` converts
+    /// one item into its bound value. All chunks run in a single
+    /// transaction; returns the total row count affected.
+    pub fn execute_chunked<T>(
+        &mut self,
+        sql_template_fn: impl Fn(&str) -> String,
+        reserved_params: &[&dyn rusqlite::ToSql],
+        items: &[T],
+        bind_fn: impl Fn(&T) -> Box<dyn rusqlite::ToSql>,
+    ) -> rusqlite::Result<usize> {
+        if items.is_empty() {
+            return Ok(0);
+        }
+
+        let max_items_per_chunk = SQLITE_MAX_VARIABLE_NUMBER
+            .saturating_sub(reserved_params.len())
+            .max(1);
+
+        let tx = self.conn.transaction()?;
+        let mut total = 0usize;
+
+        for chunk in items.chunks(max_items_per_chunk) {
+            let placeholders = std::iter::repeat("?")
+                .take(chunk.len())
+                .collect::<Vec<_>>()
+                .join(",");
+            let sql = sql_template_fn(&placeholders);
+            let mut stmt = tx.prepare_cached(&sql)?;
+
+            let item_params: Vec<Box<dyn rusqlite::ToSql>> = chunk.iter().map(&bind_fn).collect();
+            let all_params: Vec<&dyn rusqlite::ToSql> = reserved_params
+                .iter()
+                .copied()
+                .chain(item_params.iter().map(|p| p.as_ref()))
+                .collect();
+
+            total += stmt.execute(all_params.as_slice())?;
+        }
+
+        tx.commit()?;
+        Ok(total)
+    }
 }
 
 impl SqlExecutor for SqliteContext {
@@ -49,6 +191,99 @@ impl SqlExecutor for SqliteContext {
     fn execute(&mut self, sql: &str) -> Result<(), Self::Error> {
         self.conn.execute_batch(sql)
     }
+
+    fn before_post_deploy(&mut self, version: u64) -> Result<(), Self::Error> {
+        let Some(cfg) = &self.post_deploy_backup else {
+            return Ok(());
+        };
+        let path = cfg
+            .dir
+            .join(format!("backup-v{version}-{}.sqlite3", unix_now()));
+        backup_connection(&self.conn, path, cfg.step_pages, |_| {})
+    }
+
+    #[cfg(feature = "session")]
+    fn apply_tracked(&mut self, version: u64, sql: &str) -> Result<(), Self::Error> {
+        self.apply_with_session(version, sql)
+    }
+
+    #[cfg(feature = "session")]
+    fn rollback_tracked(&mut self, version: u64) -> Result<bool, Self::Error> {
+        self.ensure_changeset_table()?;
+        let has_changeset = self
+            .conn
+            .query_row(
+                "SELECT 1 FROM _migration_changesets WHERE version = ?1",
+                params![version as i64],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        if !has_changeset {
+            return Ok(false);
+        }
+        self.rollback_session(version)?;
+        Ok(true)
+    }
+}
+
+#[cfg(feature = "session")]
+impl SqliteContext {
+    /// Run `up_sql` for `version` with a `Session` attached to every table,
+    /// recording the resulting changeset so [`rollback_session`] can later
+    /// undo it without a hand-written `down_sql`. Only row-level changes are
+    /// captured, not schema DDL.
+    pub fn apply_with_session(&mut self, version: u64, up_sql: &str) -> rusqlite::Result<()> {
+        self.ensure_changeset_table()?;
+
+        let mut session = rusqlite::session::Session::new(&self.conn)?;
+        session.attach(None)?;
+
+        self.conn.execute_batch(up_sql)?;
+
+        let mut changeset = Vec::new();
+        session.changeset_strm(&mut changeset)?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO _migration_changesets (version, changeset) VALUES (?1, ?2)",
+            params![version as i64, changeset],
+        )?;
+        Ok(())
+    }
+
+    /// Undo the changes recorded by [`apply_with_session`] for `version`, by
+    /// inverting the stored changeset and re-applying it. Aborts on the
+    /// first conflicting row.
+    pub fn rollback_session(&mut self, version: u64) -> rusqlite::Result<()> {
+        let stored: Vec<u8> = self.conn.query_row(
+            "SELECT changeset FROM _migration_changesets WHERE version = ?1",
+            params![version as i64],
+            |row| row.get(0),
+        )?;
+
+        let mut inverted = Vec::new();
+        rusqlite::session::invert_strm(&mut stored.as_slice(), &mut inverted)?;
+
+        self.conn.apply_strm(
+            &mut inverted.as_slice(),
+            None::<fn(&str) -> bool>,
+            |_conflict_type, _item| rusqlite::session::ConflictAction::Abort,
+        )?;
+
+        self.conn.execute(
+            "DELETE FROM _migration_changesets WHERE version = ?1",
+            params![version as i64],
+        )?;
+        Ok(())
+    }
+
+    fn ensure_changeset_table(&self) -> rusqlite::Result<()> {
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS _migration_changesets (
+                version INTEGER PRIMARY KEY,
+                changeset BLOB NOT NULL
+            );",
+        )
+    }
 }
 
 /// SQLite-backed migration store.
@@ -85,11 +320,15 @@ impl SqliteStore {
         self.conn
     }
 
-    fn now() -> u64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0)
+    /// Copy this database to `dest_path` incrementally, `step_pages` pages
+    /// at a time, reporting progress through `progress`.
+    pub fn backup_to(
+        &self,
+        dest_path: impl AsRef<Path>,
+        step_pages: i32,
+        progress: impl FnMut(Progress),
+    ) -> rusqlite::Result<()> {
+        backup_connection(&self.conn, dest_path, step_pages, progress)
     }
 }
 
@@ -100,10 +339,12 @@ impl MigrationStore for SqliteStore {
                 "CREATE TABLE IF NOT EXISTS _migrations (
                     version INTEGER PRIMARY KEY,
                     name TEXT NOT NULL,
-                    applied_at INTEGER NOT NULL
+                    applied_at INTEGER NOT NULL,
+                    checksum TEXT NOT NULL DEFAULT ''
                 );",
             )
-            .map_err(|e| lib_migrations_core::Error::store(e.to_string()))
+            .map_err(|e| lib_migrations_core::Error::store(e.to_string()))?;
+        ensure_checksum_column(&self.conn).map_err(|e| lib_migrations_core::Error::store(e.to_string()))
     }
 
     fn applied(&self) -> lib_migrations_core::Result<Vec<MigrationRecord>> {
@@ -131,7 +372,7 @@ impl MigrationStore for SqliteStore {
         self.conn
             .execute(
                 "INSERT INTO _migrations (version, name, applied_at) VALUES (?1, ?2, ?3)",
-                params![version as i64, name, Self::now() as i64],
+                params![version as i64, name, unix_now() as i64],
             )
             .map_err(|e| lib_migrations_core::Error::store(e.to_string()))?;
         Ok(())
@@ -148,12 +389,1183 @@ impl MigrationStore for SqliteStore {
     }
 }
 
+impl SqliteStore {
+    /// Record `migration`'s checksum on its `_migrations` row, so a later
+    /// [`verify_checksums`](SqliteStore::verify_checksums) call can detect
+    /// drift. `MigrationRunner::migrate()` never calls this itself — its
+    /// trait `mark_applied(version, name)` has no way to carry a checksum —
+    /// so call it yourself for each migration right after `migrate()` applies
+    /// it. It's an `INSERT OR REPLACE`, so it works whether `migrate()`
+    /// already inserted the row or not.
+    pub fn mark_applied_checksummed(
+        &mut self,
+        migration: &SqlMigration,
+    ) -> lib_migrations_core::Result<()> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO _migrations (version, name, applied_at, checksum)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    migration.version() as i64,
+                    migration.name(),
+                    unix_now() as i64,
+                    migration.checksum()
+                ],
+            )
+            .map_err(|e| lib_migrations_core::Error::store(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Compare each migration in `migrations` against the checksum stored
+    /// for it, returning [`Error::ChecksumMismatch`](crate::Error::ChecksumMismatch)
+    /// on the first mismatch. Like `mark_applied_checksummed`, nothing calls
+    /// this automatically — call it yourself; `MigrationRunner::init()`/
+    /// `migrate()` skip it entirely.
+    ///
+    /// Migrations not yet applied, and applied rows with no stored checksum
+    /// (plain `mark_applied`, or never backfilled), don't count as drift.
+    pub fn verify_checksums(&self, migrations: &[SqlMigration]) -> crate::Result<()> {
+        for migration in migrations {
+            let stored: Option<String> = self
+                .conn
+                .query_row(
+                    "SELECT checksum FROM _migrations WHERE version = ?1",
+                    params![migration.version() as i64],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(crate::Error::from)?;
+
+            if let Some(checksum) = stored {
+                if !checksum.is_empty() && checksum != migration.checksum() {
+                    return Err(crate::Error::ChecksumMismatch {
+                        version: migration.version(),
+                        expected: migration.checksum().to_string(),
+                        found: checksum,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A combined SQL executor and migration store backed by a single SQLite
+/// connection.
+///
+/// `SqliteContext` and `SqliteStore` hold independent `Connection`s, so a
+/// migration's `up_sql` and the bookkeeping row written to `_migrations` are
+/// never atomic with each other: a crash (or a later statement failing)
+/// between the two can leave the schema changed but the history table
+/// unaware of it, or vice versa. `SqliteMigrationDb` instead wraps one
+/// connection and implements both `SqlExecutor` and `MigrationStore`,
+/// wrapping each migration's SQL together with its `_migrations` row update
+/// in a single transaction (a `SAVEPOINT` if a transaction is already open),
+/// so the two either both land or both roll back.
+///
+/// `MigrationRunner` takes ownership of the store and separately borrows the
+/// executor context, so `SqliteMigrationDb` is cheaply `Clone`: every clone
+/// shares the same underlying connection and transaction depth, which is
+/// what lets one `SqliteMigrationDb` serve as both `runner`'s store and the
+/// `ctx` passed to `runner.migrate(&mut ctx)`.
+#[derive(Clone)]
+pub struct SqliteMigrationDb {
+    conn: Rc<RefCell<Connection>>,
+    tx_depth: Rc<Cell<u32>>,
+    post_deploy_backup: Rc<RefCell<Option<PostDeployBackupConfig>>>,
+}
+
+impl SqliteMigrationDb {
+    /// Open a SQLite database for combined migration execution and tracking
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, rusqlite::Error> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "PRAGMA journal_mode=WAL;
+             PRAGMA synchronous=NORMAL;
+             PRAGMA foreign_keys=ON;",
+        )?;
+        Ok(Self {
+            conn: Rc::new(RefCell::new(conn)),
+            tx_depth: Rc::new(Cell::new(0)),
+            post_deploy_backup: Rc::new(RefCell::new(None)),
+        })
+    }
+
+    /// Open an in-memory SQLite database
+    pub fn open_in_memory() -> Result<Self, rusqlite::Error> {
+        let conn = Connection::open_in_memory()?;
+        Ok(Self {
+            conn: Rc::new(RefCell::new(conn)),
+            tx_depth: Rc::new(Cell::new(0)),
+            post_deploy_backup: Rc::new(RefCell::new(None)),
+        })
+    }
+
+    /// Run `f` with a borrow of the underlying connection.
+    pub fn with_connection<T>(&self, f: impl FnOnce(&Connection) -> T) -> T {
+        f(&self.conn.borrow())
+    }
+
+    /// Copy this database to `dest_path` incrementally, `step_pages` pages
+    /// at a time, reporting progress through `progress`.
+    pub fn backup_to(
+        &self,
+        dest_path: impl AsRef<Path>,
+        step_pages: i32,
+        progress: impl FnMut(Progress),
+    ) -> rusqlite::Result<()> {
+        backup_connection(&self.conn.borrow(), dest_path, step_pages, progress)
+    }
+
+    /// Snapshot the database to a timestamped file under `dir` before every
+    /// migration whose `phase()` is `Phase::PostDeploy`, which is where
+    /// irreversible drops typically live. Off by default.
+    pub fn with_post_deploy_backups(self, dir: impl Into<PathBuf>, step_pages: i32) -> Self {
+        *self.post_deploy_backup.borrow_mut() = Some(PostDeployBackupConfig {
+            dir: dir.into(),
+            step_pages,
+        });
+        self
+    }
+
+    /// Begin a transaction, or a nested `SAVEPOINT` if one is already open.
+    fn begin(&self) -> rusqlite::Result<()> {
+        let depth = self.tx_depth.get();
+        if depth == 0 {
+            self.conn.borrow().execute_batch("BEGIN")?;
+        } else {
+            self.conn
+                .borrow()
+                .execute_batch(&format!("SAVEPOINT _lm_sp_{depth}"))?;
+        }
+        self.tx_depth.set(depth + 1);
+        Ok(())
+    }
+
+    /// Commit the innermost open transaction/savepoint. A no-op when none is
+    /// open, since the preceding statement has then already autocommitted.
+    fn commit(&self) -> rusqlite::Result<()> {
+        let Some(depth) = self.tx_depth.get().checked_sub(1) else {
+            return Ok(());
+        };
+        self.tx_depth.set(depth);
+        if depth == 0 {
+            self.conn.borrow().execute_batch("COMMIT")
+        } else {
+            self.conn
+                .borrow()
+                .execute_batch(&format!("RELEASE _lm_sp_{depth}"))
+        }
+    }
+
+    /// Roll back the innermost open transaction/savepoint.
+    fn rollback(&self) {
+        let depth = self.tx_depth.get();
+        if depth == 0 {
+            return;
+        }
+        let depth = depth - 1;
+        self.tx_depth.set(depth);
+        let _ = if depth == 0 {
+            self.conn.borrow().execute_batch("ROLLBACK")
+        } else {
+            self.conn.borrow().execute_batch(&format!(
+                "ROLLBACK TO _lm_sp_{depth}; RELEASE _lm_sp_{depth}"
+            ))
+        };
+    }
+
+    /// Roll back every open transaction/savepoint, unwinding `tx_depth` to 0.
+    /// A plain [`rollback`](SqliteMigrationDb::rollback) only undoes the
+    /// innermost level, which is correct for `execute`'s own failures but
+    /// would leave an outer transaction dangling open forever if a nested
+    /// call (e.g. `execute_chunked` after `execute`) failed instead.
+    fn rollback_all(&self) {
+        while self.tx_depth.get() > 0 {
+            self.rollback();
+        }
+    }
+
+    /// Like [`SqliteContext::execute_chunked`], but nests as a `SAVEPOINT`
+    /// via `begin`/`commit` if called while an `execute`-opened transaction
+    /// is already in flight, rather than starting a second top-level
+    /// transaction on the same connection. On failure it unwinds every open
+    /// level (`rollback_all`), not just its own, so a nested failure can't
+    /// leave that outer transaction dangling open.
+    pub fn execute_chunked<T>(
+        &mut self,
+        sql_template_fn: impl Fn(&str) -> String,
+        reserved_params: &[&dyn rusqlite::ToSql],
+        items: &[T],
+        bind_fn: impl Fn(&T) -> Box<dyn rusqlite::ToSql>,
+    ) -> rusqlite::Result<usize> {
+        if items.is_empty() {
+            return Ok(0);
+        }
+
+        let max_items_per_chunk = SQLITE_MAX_VARIABLE_NUMBER
+            .saturating_sub(reserved_params.len())
+            .max(1);
+
+        self.begin()?;
+
+        let mut run = || -> rusqlite::Result<usize> {
+            let mut total = 0usize;
+            for chunk in items.chunks(max_items_per_chunk) {
+                let placeholders = std::iter::repeat("?")
+                    .take(chunk.len())
+                    .collect::<Vec<_>>()
+                    .join(",");
+                let sql = sql_template_fn(&placeholders);
+                let conn = self.conn.borrow();
+                let mut stmt = conn.prepare_cached(&sql)?;
+
+                let item_params: Vec<Box<dyn rusqlite::ToSql>> =
+                    chunk.iter().map(&bind_fn).collect();
+                let all_params: Vec<&dyn rusqlite::ToSql> = reserved_params
+                    .iter()
+                    .copied()
+                    .chain(item_params.iter().map(|p| p.as_ref()))
+                    .collect();
+
+                total += stmt.execute(all_params.as_slice())?;
+            }
+            Ok(total)
+        };
+
+        match run() {
+            Ok(total) => {
+                self.commit()?;
+                Ok(total)
+            }
+            Err(e) => {
+                self.rollback_all();
+                Err(e)
+            }
+        }
+    }
+}
+
+impl SqlExecutor for SqliteMigrationDb {
+    type Error = rusqlite::Error;
+
+    /// Run `sql` inside a transaction that stays open until the matching
+    /// `mark_applied`/`mark_rolled_back` call commits it. If `sql` fails,
+    /// the transaction is rolled back immediately.
+    fn execute(&mut self, sql: &str) -> Result<(), Self::Error> {
+        self.begin()?;
+        match self.conn.borrow().execute_batch(sql) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                self.rollback();
+                Err(e)
+            }
+        }
+    }
+
+    fn before_post_deploy(&mut self, version: u64) -> Result<(), Self::Error> {
+        let Some((path, step_pages)) = self.post_deploy_backup.borrow().as_ref().map(|cfg| {
+            let path = cfg
+                .dir
+                .join(format!("backup-v{version}-{}.sqlite3", unix_now()));
+            (path, cfg.step_pages)
+        }) else {
+            return Ok(());
+        };
+        backup_connection(&self.conn.borrow(), path, step_pages, |_| {})
+    }
+
+    /// Runs `sql` for `version` via [`apply_with_session`](SqliteMigrationDb::apply_with_session)
+    /// inside the same `begin`/`rollback` transaction tracking as `execute`,
+    /// leaving the transaction open for `mark_applied` to commit.
+    #[cfg(feature = "session")]
+    fn apply_tracked(&mut self, version: u64, sql: &str) -> Result<(), Self::Error> {
+        self.begin()?;
+        match self.apply_with_session(version, sql) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                self.rollback();
+                Err(e)
+            }
+        }
+    }
+
+    /// Runs [`rollback_session`](SqliteMigrationDb::rollback_session) for
+    /// `version` inside the same `begin`/`rollback` transaction tracking as
+    /// `execute`, leaving the transaction open for `mark_rolled_back` to
+    /// commit. Returns `Ok(false)` without opening a transaction if no
+    /// changeset was recorded for `version`.
+    #[cfg(feature = "session")]
+    fn rollback_tracked(&mut self, version: u64) -> Result<bool, Self::Error> {
+        self.ensure_changeset_table()?;
+        let has_changeset = self
+            .conn
+            .borrow()
+            .query_row(
+                "SELECT 1 FROM _migration_changesets WHERE version = ?1",
+                params![version as i64],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        if !has_changeset {
+            return Ok(false);
+        }
+
+        self.begin()?;
+        match self.rollback_session(version) {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                self.rollback();
+                Err(e)
+            }
+        }
+    }
+}
+
+impl MigrationStore for SqliteMigrationDb {
+    fn init(&mut self) -> lib_migrations_core::Result<()> {
+        self.conn
+            .borrow()
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS _migrations (
+                    version INTEGER PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    applied_at INTEGER NOT NULL,
+                    checksum TEXT NOT NULL DEFAULT ''
+                );",
+            )
+            .map_err(|e| lib_migrations_core::Error::store(e.to_string()))?;
+        ensure_checksum_column(&self.conn.borrow())
+            .map_err(|e| lib_migrations_core::Error::store(e.to_string()))
+    }
+
+    fn applied(&self) -> lib_migrations_core::Result<Vec<MigrationRecord>> {
+        let conn = self.conn.borrow();
+        let mut stmt = conn
+            .prepare("SELECT version, name, applied_at FROM _migrations ORDER BY version")
+            .map_err(|e| lib_migrations_core::Error::store(e.to_string()))?;
+
+        let records = stmt
+            .query_map([], |row| {
+                Ok(MigrationRecord {
+                    version: row.get::<_, i64>(0)? as u64,
+                    name: row.get(1)?,
+                    applied_at: row.get::<_, i64>(2)? as u64,
+                })
+            })
+            .map_err(|e| lib_migrations_core::Error::store(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| lib_migrations_core::Error::store(e.to_string()))?;
+
+        Ok(records)
+    }
+
+    /// Insert the `_migrations` row for `version` and commit the
+    /// transaction opened by the preceding `execute` call, so the schema
+    /// change and the history row land atomically.
+    fn mark_applied(&mut self, version: u64, name: &str) -> lib_migrations_core::Result<()> {
+        let result = self.conn.borrow().execute(
+            "INSERT INTO _migrations (version, name, applied_at) VALUES (?1, ?2, ?3)",
+            params![version as i64, name, unix_now() as i64],
+        );
+        match result {
+            Ok(_) => self
+                .commit()
+                .map_err(|e| lib_migrations_core::Error::store(e.to_string())),
+            Err(e) => {
+                self.rollback();
+                Err(lib_migrations_core::Error::store(e.to_string()))
+            }
+        }
+    }
+
+    /// Delete the `_migrations` row for `version` and commit the
+    /// transaction opened by the preceding `execute` call, so the rollback
+    /// SQL and the history removal land atomically.
+    fn mark_rolled_back(&mut self, version: u64) -> lib_migrations_core::Result<()> {
+        let result = self.conn.borrow().execute(
+            "DELETE FROM _migrations WHERE version = ?1",
+            params![version as i64],
+        );
+        match result {
+            Ok(_) => self
+                .commit()
+                .map_err(|e| lib_migrations_core::Error::store(e.to_string())),
+            Err(e) => {
+                self.rollback();
+                Err(lib_migrations_core::Error::store(e.to_string()))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "session")]
+impl SqliteMigrationDb {
+    /// Run `up_sql` for `version` with a `Session` attached to every table,
+    /// recording the resulting changeset so [`rollback_session`] can later
+    /// undo it without a hand-written `down_sql`. See
+    /// `SqliteContext::apply_with_session` for the reversibility caveats.
+    pub fn apply_with_session(&mut self, version: u64, up_sql: &str) -> rusqlite::Result<()> {
+        self.ensure_changeset_table()?;
+
+        let conn = self.conn.borrow();
+        let mut session = rusqlite::session::Session::new(&conn)?;
+        session.attach(None)?;
+
+        conn.execute_batch(up_sql)?;
+
+        let mut changeset = Vec::new();
+        session.changeset_strm(&mut changeset)?;
+        conn.execute(
+            "INSERT OR REPLACE INTO _migration_changesets (version, changeset) VALUES (?1, ?2)",
+            params![version as i64, changeset],
+        )?;
+        Ok(())
+    }
+
+    /// Undo the changes recorded by [`apply_with_session`] for `version`, by
+    /// inverting the stored changeset and re-applying it. Aborts on the
+    /// first conflicting row.
+    pub fn rollback_session(&mut self, version: u64) -> rusqlite::Result<()> {
+        let conn = self.conn.borrow();
+        let stored: Vec<u8> = conn.query_row(
+            "SELECT changeset FROM _migration_changesets WHERE version = ?1",
+            params![version as i64],
+            |row| row.get(0),
+        )?;
+
+        let mut inverted = Vec::new();
+        rusqlite::session::invert_strm(&mut stored.as_slice(), &mut inverted)?;
+
+        conn.apply_strm(
+            &mut inverted.as_slice(),
+            None::<fn(&str) -> bool>,
+            |_conflict_type, _item| rusqlite::session::ConflictAction::Abort,
+        )?;
+
+        conn.execute(
+            "DELETE FROM _migration_changesets WHERE version = ?1",
+            params![version as i64],
+        )?;
+        Ok(())
+    }
+
+    fn ensure_changeset_table(&self) -> rusqlite::Result<()> {
+        self.conn.borrow().execute_batch(
+            "CREATE TABLE IF NOT EXISTS _migration_changesets (
+                version INTEGER PRIMARY KEY,
+                changeset BLOB NOT NULL
+            );",
+        )
+    }
+}
+
+impl SqliteMigrationDb {
+    /// Commit the transaction opened by the preceding `execute` call,
+    /// recording `migration`'s checksum on its `_migrations` row instead of
+    /// the usual empty one, so a later
+    /// [`verify_checksums`](SqliteMigrationDb::verify_checksums) call can
+    /// detect drift.
+    ///
+    /// `MigrationRunner::migrate()` always calls the plain
+    /// [`MigrationStore::mark_applied`] (which has no way to receive a
+    /// checksum), so it never gets checksums recorded this way — to use
+    /// this, drive `migration.apply(&mut ctx)` and this method yourself in
+    /// place of `runner.migrate()`, not after it.
+    pub fn mark_applied_checksummed(
+        &mut self,
+        migration: &SqlMigration,
+    ) -> lib_migrations_core::Result<()> {
+        let result = self.conn.borrow().execute(
+            "INSERT INTO _migrations (version, name, applied_at, checksum)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                migration.version() as i64,
+                migration.name(),
+                unix_now() as i64,
+                migration.checksum()
+            ],
+        );
+        match result {
+            Ok(_) => self
+                .commit()
+                .map_err(|e| lib_migrations_core::Error::store(e.to_string())),
+            Err(e) => {
+                self.rollback();
+                Err(lib_migrations_core::Error::store(e.to_string()))
+            }
+        }
+    }
+
+    /// Compare each migration in `migrations` against the checksum stored
+    /// for it, returning [`Error::ChecksumMismatch`](crate::Error::ChecksumMismatch)
+    /// on the first mismatch. Like `mark_applied_checksummed`, nothing calls
+    /// this automatically — call it yourself; `MigrationRunner::init()`/
+    /// `migrate()` skip it entirely.
+    ///
+    /// Migrations not yet applied, and applied rows with no stored checksum
+    /// (plain `mark_applied`), don't count as drift.
+    pub fn verify_checksums(&self, migrations: &[SqlMigration]) -> crate::Result<()> {
+        for migration in migrations {
+            let stored: Option<String> = self
+                .conn
+                .borrow()
+                .query_row(
+                    "SELECT checksum FROM _migrations WHERE version = ?1",
+                    params![migration.version() as i64],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(crate::Error::from)?;
+
+            if let Some(checksum) = stored {
+                if !checksum.is_empty() && checksum != migration.checksum() {
+                    return Err(crate::Error::ChecksumMismatch {
+                        version: migration.version(),
+                        expected: migration.checksum().to_string(),
+                        found: checksum,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A `MigrationStore` that tracks progress via SQLite's `PRAGMA user_version`
+/// integer instead of a `_migrations` history table — no extra table, but
+/// [`UserVersionStore::applied`] can only synthesize placeholder records (no
+/// name, no timestamp).
+///
+/// Versions **must** be small, sequential integers starting at `1`, since
+/// `applied()` synthesizes one record per version up to the current value
+/// and `user_version` itself is a 32-bit signed field; `mark_applied` rejects
+/// anything outside `1..=i32::MAX` or out of sequence rather than silently
+/// truncating.
+pub struct UserVersionStore {
+    conn: Connection,
+}
+
+impl UserVersionStore {
+    /// Open a SQLite database for user_version-based migration tracking
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, rusqlite::Error> {
+        let conn = Connection::open(path)?;
+        Ok(Self { conn })
+    }
+
+    /// Open an in-memory SQLite database
+    pub fn open_in_memory() -> Result<Self, rusqlite::Error> {
+        let conn = Connection::open_in_memory()?;
+        Ok(Self { conn })
+    }
+
+    /// Get the underlying connection
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Consume and return the underlying connection
+    pub fn into_connection(self) -> Connection {
+        self.conn
+    }
+
+    fn read_user_version(&self) -> lib_migrations_core::Result<u64> {
+        self.conn
+            .query_row("PRAGMA user_version", [], |row| row.get::<_, i64>(0))
+            .map(|v| v as u64)
+            .map_err(|e| lib_migrations_core::Error::store(e.to_string()))
+    }
+
+    /// Write `version` to `PRAGMA user_version`, rejecting anything that
+    /// doesn't fit in SQLite's 32-bit signed `user_version` field instead of
+    /// letting it silently wrap or truncate.
+    fn write_user_version(&self, version: u64) -> lib_migrations_core::Result<()> {
+        if version > i32::MAX as u64 {
+            return Err(lib_migrations_core::Error::store(format!(
+                "version {version} exceeds PRAGMA user_version's range (0..={}); \
+                 UserVersionStore requires small, sequential versions",
+                i32::MAX
+            )));
+        }
+        self.conn
+            .execute_batch(&format!("PRAGMA user_version = {version}"))
+            .map_err(|e| lib_migrations_core::Error::store(e.to_string()))
+    }
+}
+
+impl MigrationStore for UserVersionStore {
+    fn init(&mut self) -> lib_migrations_core::Result<()> {
+        // No table to create; `PRAGMA user_version` already defaults to 0.
+        Ok(())
+    }
+
+    /// Synthesize one record per version from `1` to the current
+    /// `user_version`, since that integer is all the history this store
+    /// keeps. `name` is always empty and `applied_at` is always `0`.
+    fn applied(&self) -> lib_migrations_core::Result<Vec<MigrationRecord>> {
+        let version = self.read_user_version()?;
+        Ok((1..=version)
+            .map(|version| MigrationRecord {
+                version,
+                name: String::new(),
+                applied_at: 0,
+            })
+            .collect())
+    }
+
+    /// Requires `version` to be the next sequential version after the
+    /// current one (i.e. `current_version() + 1`), since `applied()` and
+    /// `PRAGMA user_version` can only represent a dense run starting at `1`.
+    fn mark_applied(&mut self, version: u64, _name: &str) -> lib_migrations_core::Result<()> {
+        let current = self.read_user_version()?;
+        if version != current + 1 {
+            return Err(lib_migrations_core::Error::store(format!(
+                "UserVersionStore requires migrations to apply in order starting at 1: \
+                 current version is {current}, expected {}, got {version}",
+                current + 1
+            )));
+        }
+        self.write_user_version(version)
+    }
+
+    /// Requires `version` to be the current version, since rolling back any
+    /// other version would make the dense `1..=current_version` history this
+    /// store assumes inconsistent with what was actually applied.
+    fn mark_rolled_back(&mut self, version: u64) -> lib_migrations_core::Result<()> {
+        let current = self.read_user_version()?;
+        if version != current {
+            return Err(lib_migrations_core::Error::store(format!(
+                "UserVersionStore can only roll back the current version ({current}), got {version}"
+            )));
+        }
+        self.write_user_version(version.saturating_sub(1))
+    }
+
+    fn current_version(&self) -> lib_migrations_core::Result<u64> {
+        self.read_user_version()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::SqlMigration;
     use lib_migrations_core::MigrationRunner;
 
+    #[test]
+    #[cfg(feature = "session")]
+    fn test_session_rollback_undoes_row_changes() {
+        let mut ctx = SqliteContext::open_in_memory().unwrap();
+        ctx.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT)")
+            .unwrap();
+
+        ctx.apply_with_session(1, "INSERT INTO t (id, name) VALUES (1, 'a')")
+            .unwrap();
+
+        let name: String = ctx
+            .connection()
+            .query_row("SELECT name FROM t WHERE id = 1", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(name, "a");
+
+        ctx.rollback_session(1).unwrap();
+
+        let count: i64 = ctx
+            .connection()
+            .query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "session")]
+    fn test_migration_runner_rolls_back_session_migration_with_no_down_sql() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let mut ctx = SqliteContext::open_in_memory().unwrap();
+
+        let mut runner = MigrationRunner::new(store).add(
+            SqlMigration::new(
+                1,
+                "create_t",
+                "CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT)",
+            )
+            .with_down("DROP TABLE t"),
+        );
+
+        runner.init().unwrap();
+        runner.migrate(&mut ctx).unwrap();
+
+        // A second, data-only migration with no down_sql, reversible only
+        // through the session changeset recorded by `apply_tracked`.
+        let mut runner = runner.add(
+            SqlMigration::new(2, "seed_t", "INSERT INTO t (id, name) VALUES (1, 'a')")
+                .with_session_rollback(),
+        );
+        runner.migrate(&mut ctx).unwrap();
+        assert_eq!(runner.current_version().unwrap(), 2);
+
+        let count: i64 = ctx
+            .connection()
+            .query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+
+        runner.migrate_to(&mut ctx, 1).unwrap();
+        assert_eq!(runner.current_version().unwrap(), 1);
+
+        let count: i64 = ctx
+            .connection()
+            .query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "session")]
+    fn test_migration_db_runner_rolls_back_session_migration_with_no_down_sql() {
+        let mut db = SqliteMigrationDb::open_in_memory().unwrap();
+        db.init().unwrap();
+        let mut ctx = db.clone();
+
+        let mut runner = MigrationRunner::new(db).add(
+            SqlMigration::new(
+                1,
+                "create_t",
+                "CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT)",
+            )
+            .with_down("DROP TABLE t"),
+        );
+
+        runner.init().unwrap();
+        runner.migrate(&mut ctx).unwrap();
+
+        let mut runner = runner.add(
+            SqlMigration::new(2, "seed_t", "INSERT INTO t (id, name) VALUES (1, 'a')")
+                .with_session_rollback(),
+        );
+        runner.migrate(&mut ctx).unwrap();
+        assert_eq!(runner.current_version().unwrap(), 2);
+
+        let count: i64 = ctx
+            .with_connection(|conn| conn.query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0)))
+            .unwrap();
+        assert_eq!(count, 1);
+
+        runner.migrate_to(&mut ctx, 1).unwrap();
+        assert_eq!(runner.current_version().unwrap(), 1);
+
+        let count: i64 = ctx
+            .with_connection(|conn| conn.query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0)))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_execute_chunked_respects_variable_limit() {
+        let mut ctx = SqliteContext::open_in_memory().unwrap();
+        ctx.execute("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+            .unwrap();
+
+        let ids: Vec<i64> = (1..=1500).collect();
+        let values_sql = ids
+            .iter()
+            .map(|id| format!("({id})"))
+            .collect::<Vec<_>>()
+            .join(",");
+        ctx.execute(&format!("INSERT INTO t (id) VALUES {values_sql}"))
+            .unwrap();
+
+        let deleted = ctx
+            .execute_chunked(
+                |placeholders| format!("DELETE FROM t WHERE id IN ({placeholders})"),
+                &[],
+                &ids,
+                |id: &i64| Box::new(*id) as Box<dyn rusqlite::ToSql>,
+            )
+            .unwrap();
+
+        assert_eq!(deleted, 1500);
+        let remaining: i64 = ctx
+            .connection()
+            .query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+
+    #[test]
+    fn test_execute_chunked_with_reserved_params() {
+        let mut ctx = SqliteContext::open_in_memory().unwrap();
+        ctx.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, status TEXT)")
+            .unwrap();
+
+        let ids: Vec<i64> = vec![1, 2, 3];
+        for id in &ids {
+            ctx.execute(&format!("INSERT INTO t (id, status) VALUES ({id}, 'old')"))
+                .unwrap();
+        }
+
+        let updated = ctx
+            .execute_chunked(
+                |placeholders| format!("UPDATE t SET status = ? WHERE id IN ({placeholders})"),
+                &[&"new"],
+                &ids,
+                |id: &i64| Box::new(*id) as Box<dyn rusqlite::ToSql>,
+            )
+            .unwrap();
+
+        assert_eq!(updated, 3);
+        let status: String = ctx
+            .connection()
+            .query_row("SELECT status FROM t WHERE id = 1", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(status, "new");
+    }
+
+    #[test]
+    fn test_migration_db_execute_chunked_respects_variable_limit() {
+        let mut db = SqliteMigrationDb::open_in_memory().unwrap();
+        db.with_connection(|conn| conn.execute_batch("CREATE TABLE t (id INTEGER PRIMARY KEY)"))
+            .unwrap();
+
+        let ids: Vec<i64> = (1..=1500).collect();
+        let values_sql = ids
+            .iter()
+            .map(|id| format!("({id})"))
+            .collect::<Vec<_>>()
+            .join(",");
+        db.with_connection(|conn| {
+            conn.execute_batch(&format!("INSERT INTO t (id) VALUES {values_sql}"))
+        })
+        .unwrap();
+
+        let deleted = db
+            .execute_chunked(
+                |placeholders| format!("DELETE FROM t WHERE id IN ({placeholders})"),
+                &[],
+                &ids,
+                |id: &i64| Box::new(*id) as Box<dyn rusqlite::ToSql>,
+            )
+            .unwrap();
+
+        assert_eq!(deleted, 1500);
+        let remaining: i64 = db
+            .with_connection(|conn| conn.query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0)))
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+
+    #[test]
+    fn test_migration_db_execute_chunked_nests_inside_open_migration_transaction() {
+        // Simulate a migration that, instead of a single `execute(up_sql)`
+        // call, runs a chunked backfill on `self` before `mark_applied`
+        // commits: `execute` leaves a transaction open, so `execute_chunked`
+        // must nest as a SAVEPOINT rather than trying to open a second
+        // top-level transaction on the same connection.
+        let mut db = SqliteMigrationDb::open_in_memory().unwrap();
+        db.init().unwrap();
+
+        db.execute(
+            "CREATE TABLE t (id INTEGER PRIMARY KEY);
+             INSERT INTO t (id) VALUES (1), (2), (3), (4);",
+        )
+        .unwrap();
+
+        let ids: Vec<i64> = vec![2, 4];
+        let deleted = db
+            .execute_chunked(
+                |placeholders| format!("DELETE FROM t WHERE id IN ({placeholders})"),
+                &[],
+                &ids,
+                |id: &i64| Box::new(*id) as Box<dyn rusqlite::ToSql>,
+            )
+            .unwrap();
+        assert_eq!(deleted, 2);
+
+        db.mark_applied(1, "create_and_prune_t").unwrap();
+
+        let count: i64 = db
+            .with_connection(|conn| conn.query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0)))
+            .unwrap();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_migration_db_execute_chunked_failure_unwinds_outer_transaction() {
+        // If `execute_chunked` fails while nested inside an `execute`-opened
+        // transaction, a partial rollback of just its own SAVEPOINT would
+        // leave that outer transaction open forever (nothing else is going
+        // to commit or roll it back, since the migration that called
+        // `execute` failed). `execute_chunked` must unwind all the way back
+        // to depth 0 instead.
+        let mut db = SqliteMigrationDb::open_in_memory().unwrap();
+        db.init().unwrap();
+
+        db.execute("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+            .unwrap();
+
+        let ids: Vec<i64> = vec![1];
+        let result = db.execute_chunked(
+            |_placeholders| "NOT VALID SQL".to_string(),
+            &[],
+            &ids,
+            |id: &i64| Box::new(*id) as Box<dyn rusqlite::ToSql>,
+        );
+        assert!(result.is_err());
+
+        // If the outer transaction were left dangling, this would open a
+        // SAVEPOINT on top of it instead of a fresh BEGIN, and `mark_applied`
+        // would only release that SAVEPOINT, leaving `t` in place inside the
+        // still-open outer transaction.
+        db.execute("CREATE TABLE u (id INTEGER PRIMARY KEY)")
+            .unwrap();
+        db.mark_applied(1, "create_u").unwrap();
+
+        let exists = |name: &str| {
+            db.with_connection(|conn| {
+                conn.query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name=?1",
+                    params![name],
+                    |row| row.get::<_, i64>(0),
+                )
+            })
+            .unwrap()
+        };
+        assert_eq!(exists("u"), 1);
+        assert_eq!(exists("t"), 0);
+    }
+
+    #[test]
+    fn test_user_version_store() {
+        let mut store = UserVersionStore::open_in_memory().unwrap();
+        store.init().unwrap();
+
+        assert_eq!(store.current_version().unwrap(), 0);
+        assert!(store.applied().unwrap().is_empty());
+
+        store.mark_applied(1, "first").unwrap();
+        store.mark_applied(2, "second").unwrap();
+        assert_eq!(store.current_version().unwrap(), 2);
+
+        let applied = store.applied().unwrap();
+        assert_eq!(applied.len(), 2);
+        assert_eq!(applied[0].version, 1);
+        assert_eq!(applied[1].version, 2);
+
+        store.mark_rolled_back(2).unwrap();
+        assert_eq!(store.current_version().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_user_version_store_rejects_non_sequential_version() {
+        let mut store = UserVersionStore::open_in_memory().unwrap();
+        store.init().unwrap();
+
+        // Skipping straight to 2 without applying 1 first is rejected
+        // rather than silently accepted.
+        assert!(store.mark_applied(2, "second").is_err());
+        assert_eq!(store.current_version().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_user_version_store_rejects_out_of_range_version() {
+        let mut store = UserVersionStore::open_in_memory().unwrap();
+        store.init().unwrap();
+
+        // A timestamp-style version far exceeds PRAGMA user_version's
+        // 32-bit signed range and must be rejected, not silently truncated.
+        let timestamp_version = i32::MAX as u64 + 1;
+        assert!(store.mark_applied(timestamp_version, "too big").is_err());
+        assert_eq!(store.current_version().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_user_version_store_rejects_rollback_of_non_current_version() {
+        let mut store = UserVersionStore::open_in_memory().unwrap();
+        store.init().unwrap();
+        store.mark_applied(1, "first").unwrap();
+        store.mark_applied(2, "second").unwrap();
+
+        assert!(store.mark_rolled_back(1).is_err());
+        assert_eq!(store.current_version().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_sqlite_context_backup_to() {
+        let mut ctx = SqliteContext::open_in_memory().unwrap();
+        ctx.execute("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+            .unwrap();
+        ctx.execute("INSERT INTO t (id) VALUES (42)").unwrap();
+
+        let dest = std::env::temp_dir().join(format!(
+            "lib_migrations_sql_backup_{}_{}.sqlite3",
+            std::process::id(),
+            unix_now()
+        ));
+        ctx.backup_to(&dest, 1, |_| {}).unwrap();
+
+        let copy = Connection::open(&dest).unwrap();
+        let id: i64 = copy
+            .query_row("SELECT id FROM t", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(id, 42);
+
+        let _ = std::fs::remove_file(&dest);
+    }
+
+    #[test]
+    fn test_post_deploy_migration_triggers_backup() {
+        use lib_migrations_core::{Migration, Phase};
+
+        let dir = std::env::temp_dir().join(format!(
+            "lib_migrations_sql_postdeploy_{}_{}",
+            std::process::id(),
+            unix_now()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut ctx = SqliteContext::open_in_memory()
+            .unwrap()
+            .with_post_deploy_backups(&dir, 1);
+
+        let pre = SqlMigration::new(1, "create_t", "CREATE TABLE t (id INTEGER)");
+        Migration::apply(&pre, &mut ctx).unwrap();
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        let post = SqlMigration::new(2, "drop_col", "ALTER TABLE t RENAME TO t2")
+            .phase(Phase::PostDeploy);
+        Migration::apply(&post, &mut ctx).unwrap();
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_sqlite_store_init_adds_checksum_column_to_legacy_table() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        // Simulate a database created by the pre-checksum version of this
+        // library, whose `_migrations` table had no `checksum` column.
+        store
+            .connection()
+            .execute_batch(
+                "CREATE TABLE _migrations (
+                    version INTEGER PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    applied_at INTEGER NOT NULL
+                );
+                INSERT INTO _migrations (version, name, applied_at) VALUES (1, 'first', 0);",
+            )
+            .unwrap();
+
+        let mut store = store;
+        store.init().unwrap();
+
+        let migration = SqlMigration::new(2, "second", "CREATE TABLE t (id INTEGER)");
+        store.mark_applied_checksummed(&migration).unwrap();
+        store.verify_checksums(&[migration]).unwrap();
+
+        // The pre-existing row is untouched and still reads back fine.
+        let applied = store.applied().unwrap();
+        assert_eq!(applied.len(), 2);
+        assert_eq!(applied[0].version, 1);
+    }
+
+    #[test]
+    fn test_migration_db_init_adds_checksum_column_to_legacy_table() {
+        let db = SqliteMigrationDb::open_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "CREATE TABLE _migrations (
+                    version INTEGER PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    applied_at INTEGER NOT NULL
+                );",
+            )
+        })
+        .unwrap();
+
+        let mut db = db;
+        db.init().unwrap();
+
+        let migration = SqlMigration::new(1, "first", "CREATE TABLE t (id INTEGER)");
+        db.mark_applied_checksummed(&migration).unwrap();
+        db.verify_checksums(&[migration]).unwrap();
+    }
+
+    #[test]
+    fn test_sqlite_store_checksum_drift() {
+        let mut store = SqliteStore::open_in_memory().unwrap();
+        store.init().unwrap();
+
+        let original = SqlMigration::new(1, "create_users", "CREATE TABLE users (id INTEGER)");
+        store.mark_applied_checksummed(&original).unwrap();
+        store.verify_checksums(&[original]).unwrap();
+
+        let edited = SqlMigration::new(
+            1,
+            "create_users",
+            "CREATE TABLE users (id INTEGER, name TEXT)",
+        );
+        let err = store.verify_checksums(&[edited]).unwrap_err();
+        assert!(matches!(err, crate::Error::ChecksumMismatch { version: 1, .. }));
+    }
+
+    #[test]
+    fn test_sqlite_store_checksum_ignores_unapplied_and_legacy_rows() {
+        let mut store = SqliteStore::open_in_memory().unwrap();
+        store.init().unwrap();
+
+        let pending = SqlMigration::new(1, "create_users", "CREATE TABLE users (id INTEGER)");
+        // Not yet applied: no drift.
+        store.verify_checksums(&[pending]).unwrap();
+
+        // Applied through the plain trait method, which can't carry a
+        // checksum: treated as legacy, not drift.
+        store.mark_applied(2, "legacy").unwrap();
+        let legacy = SqlMigration::new(2, "legacy", "CREATE TABLE legacy (id INTEGER)");
+        store.verify_checksums(&[legacy]).unwrap();
+    }
+
+    #[test]
+    fn test_sqlite_store_checksum_backfilled_after_plain_migrate() {
+        // Demonstrates the recommended recipe for catching drift on a store
+        // driven through a plain `MigrationRunner::migrate()`: `migrate()`
+        // itself only ever calls the checksum-less `mark_applied`, so the
+        // checksum has to be backfilled, and verified, by hand afterward.
+        let db_path = std::env::temp_dir().join(format!(
+            "lib_migrations_sql_checksum_backfill_{}_{}.sqlite3",
+            std::process::id(),
+            unix_now()
+        ));
+
+        let migration = SqlMigration::new(1, "create_users", "CREATE TABLE users (id INTEGER)");
+        let store = SqliteStore::open(&db_path).unwrap();
+        let mut runner = MigrationRunner::new(store).add(SqlMigration::new(
+            migration.version(),
+            migration.name(),
+            migration.up_sql(),
+        ));
+        runner.init().unwrap();
+        let mut ctx = SqliteContext::open(&db_path).unwrap();
+        runner.migrate(&mut ctx).unwrap();
+
+        // Reopen the same file as a fresh `SqliteStore` to get a handle back
+        // (`MigrationRunner::new` consumed the original one).
+        let mut store = SqliteStore::open(&db_path).unwrap();
+        store.mark_applied_checksummed(&migration).unwrap();
+        store.verify_checksums(&[migration]).unwrap();
+
+        let edited = SqlMigration::new(
+            1,
+            "create_users",
+            "CREATE TABLE users (id INTEGER, name TEXT)",
+        );
+        let err = store.verify_checksums(&[edited]).unwrap_err();
+        assert!(matches!(err, crate::Error::ChecksumMismatch { version: 1, .. }));
+
+        let _ = std::fs::remove_file(&db_path);
+    }
+
     #[test]
     fn test_sqlite_store() {
         let mut store = SqliteStore::open_in_memory().unwrap();
@@ -188,6 +1600,60 @@ mod tests {
         assert_eq!(count, 1);
     }
 
+    #[test]
+    fn test_migration_db_atomic_apply() {
+        let mut db = SqliteMigrationDb::open_in_memory().unwrap();
+        db.init().unwrap();
+        let mut ctx = db.clone();
+
+        let mut runner = MigrationRunner::new(db).add(
+            SqlMigration::new(
+                1,
+                "create_users",
+                "CREATE TABLE users (id INTEGER PRIMARY KEY)",
+            )
+            .with_down("DROP TABLE users"),
+        );
+
+        runner.init().unwrap();
+        let count = runner.migrate(&mut ctx).unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(runner.current_version().unwrap(), 1);
+
+        let exists: i64 = ctx
+            .with_connection(|conn| {
+                conn.query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='users'",
+                    [],
+                    |row| row.get(0),
+                )
+            })
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
+
+    #[test]
+    fn test_migration_db_rolls_back_on_failed_statement() {
+        let mut db = SqliteMigrationDb::open_in_memory().unwrap();
+        db.init().unwrap();
+
+        // Second statement is invalid, so the whole batch (and the would-be
+        // history row) must roll back rather than leaving the table behind.
+        let result = db.execute("CREATE TABLE t (id INTEGER); NOT VALID SQL;");
+        assert!(result.is_err());
+
+        let exists: i64 = db
+            .with_connection(|conn| {
+                conn.query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='t'",
+                    [],
+                    |row| row.get(0),
+                )
+            })
+            .unwrap();
+        assert_eq!(exists, 0);
+    }
+
     #[test]
     fn test_full_migration_flow() {
         let store = SqliteStore::open_in_memory().unwrap();